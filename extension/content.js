@@ -56,17 +56,21 @@ async function initializeSpecMonkey() {
       );
 
       // Step 4: Fetch the corresponding JSON file from searchfox
-      const searchfoxData = await fetchSearchfoxData(uri.hostname, config.extensions);
+      const {results: searchfoxData, timedOut} = await fetchSearchfoxData(
+        getSearchfoxTree(config),
+        uri.hostname,
+        config.extensions
+      );
 
       console.log(`SpecMonkey: Got ${searchfoxData.length} fragment entries.`);
 
       // Step 5: Convert the searchfox result to index format
-      const indexData = convertToIndex(uri, searchfoxData);
+      const indexData = convertToIndex(uri, searchfoxData, config);
 
       console.log(`SpecMonkey: Got ${indexData.size} fragments.`);
 
       // Step 6: Process the index data and display boxes
-      processIndexData(indexData);
+      processIndexData(indexData, config, timedOut);
     }
   } catch (error) {
     console.error(`SpecMonkey Error: ${error}`);
@@ -89,12 +93,33 @@ async function loadConfig() {
     );
   }
 
+  validatePermalinkTemplate(config);
+
   return config;
 }
 
-async function fetchSearchfoxData(domain, extensions) {
+/**
+ * Returns the Searchfox tree configured for the source repository.
+ *
+ * @param {Object} config - The parsed config.json.
+ * @returns {string} - The Searchfox tree name, e.g. `firefox-main`.
+ */
+function getSearchfoxTree(config) {
+  return config.source_repository?.searchfox_tree ?? "firefox-main";
+}
+
+/**
+ * Fetches the lines referencing `domain` from Searchfox.
+ *
+ * @param {string} tree - The Searchfox tree to search.
+ * @param {string} domain - The hostname of the spec page.
+ * @param {Array<string>} extensions - The file extensions to search.
+ * @returns {Object} - `results` as Searchfox's textual occurrences, and
+ *   `timedOut` if Searchfox gave up early and `results` are incomplete.
+ */
+async function fetchSearchfoxData(tree, domain, extensions) {
   const regex = `pathre:^[^_].*.(${extensions.join('|')})$ re:${domain}/.*`;
-  const jsonURL = `https://searchfox.org/firefox-main/search?q=${encodeURI(regex)}%23.`;
+  const jsonURL = `https://searchfox.org/${tree}/search?q=${encodeURI(regex)}%23.`;
   console.log(`SpecMonkey: Query: ${jsonURL}`)
 
   const response = await fetch(jsonURL, {headers: {"Accept": "application/json"}});
@@ -117,11 +142,15 @@ async function fetchSearchfoxData(domain, extensions) {
     );
   }
 
-  if (jsonData["*timedout*"]) {
+  const timedOut = Boolean(jsonData["*timedout*"]);
+  if (timedOut) {
     console.log(`Query timed out`);
   }
 
-  return (jsonData.normal["Textual Occurrences"] ?? []).concat(jsonData.test["Textual Occurrences"] ?? []);
+  return {
+    results: (jsonData.normal["Textual Occurrences"] ?? []).concat(jsonData.test["Textual Occurrences"] ?? []),
+    timedOut,
+  };
 }
 
 function areParenthesisBalanced(input) {
@@ -150,12 +179,187 @@ const fragment_re = String.raw`(?:${pchar_re}|[/?])*`
 // Heuristics for removing trailing `.`, `!`, `'`, `,`, `;`, and `:`.
 const heuristic_re = /^(.*?)[.!',;:]*$/;
 
-function convertToIndex(uri, searchfoxData) {
+// Markers of open work on the line of a spec link, e.g. `// TODO: Step 3`.
+const todo_re = /\b(?:TODO|FIXME|XXX)\b/;
+
+// Languages of source files, by file extension.
+const languagesByExtension = {
+  h: "C++",
+  cpp: "C++",
+  c: "C",
+  rs: "Rust",
+  js: "JavaScript",
+  mjs: "JavaScript",
+  html: "HTML",
+  xhtml: "HTML",
+  webidl: "WebIDL",
+  py: "Python",
+};
+
+/**
+ * Returns the language of a source file, based on its extension.
+ *
+ * @param {string} filepath - The path of the file.
+ * @returns {string|null} - The language, or null if the extension is unknown.
+ */
+function getLanguage(filepath) {
+  const extension = filepath.split(".").pop().toLowerCase();
+  return Object.hasOwn(languagesByExtension, extension)
+    ? languagesByExtension[extension]
+    : null;
+}
+
+/**
+ * Checks if a path lies inside one of the excluded (e.g. vendored) directories.
+ * A directory name matches at any depth, so `third_party` also excludes
+ * `media/third_party/...`. Leading and trailing slashes are ignored.
+ *
+ * @param {string} filepath - The path of the file, relative to the repository root.
+ * @param {Array<string>} excludedDirs - Directory names or paths from config.json.
+ * @returns {boolean} - Whether the file should be skipped.
+ */
+function isInExcludedDir(filepath, excludedDirs) {
+  const path = `/${filepath}`;
+  return excludedDirs
+    .map((dir) => dir.replace(/^\/+|\/+$/g, ""))
+    .some((dir) => dir && path.includes(`/${dir}/`));
+}
+
+/**
+ * Normalizes a fragment before grouping and anchor lookup, as configured by
+ * `fold_fragment_case` and `normalize_fragment_unicode` in config.json.
+ *
+ * If either is set, the fragment is percent-decoded first: fragment_re only
+ * captures ASCII, so non-ASCII fragments only ever reach us percent-encoded
+ * (e.g. `sec-caf%C3%A9`), where NFC and case folding would have no effect.
+ * Anchor ids go through the same function; decoding leaves them unchanged
+ * unless they contain a `%` escape themselves.
+ *
+ * @param {string} fragment - The fragment or anchor id, without the leading `#`.
+ * @param {Object} config - The parsed config.json.
+ * @returns {string} - The normalized fragment.
+ */
+function normalizeFragment(fragment, config) {
+  if (!config.fold_fragment_case && !config.normalize_fragment_unicode) {
+    return fragment;
+  }
+  let normalized = fragment;
+  try {
+    normalized = decodeURIComponent(normalized);
+  } catch (e) {
+    // Not valid percent-encoding, e.g. a literal `%` in the fragment.
+  }
+  if (config.normalize_fragment_unicode) {
+    normalized = normalized.normalize("NFC");
+  }
+  if (config.fold_fragment_case) {
+    normalized = normalized.toLowerCase();
+  }
+  return normalized;
+}
+
+// Comment syntax per language, see languagesByExtension. With `charLiterals`,
+// `'` only quotes a single character, since it is also used for Rust
+// lifetimes (`&'a str`) and C++14 digit separators (`1'000`).
+const cStyleComments = {line: "//", block: ["/*", "*/"]};
+const cStyleSyntax = {...cStyleComments, charLiterals: true};
+const commentSyntaxByLanguage = {
+  "C++": cStyleSyntax,
+  C: cStyleSyntax,
+  Rust: cStyleSyntax,
+  JavaScript: cStyleComments,
+  WebIDL: cStyleSyntax,
+  Python: {line: "#"},
+  HTML: {block: ["<!--", "-->"], markup: true},
+};
+
+/**
+ * Detects whether a link appeared in a line comment, block comment, string or
+ * plain text. This only looks at the line itself, so it is a heuristic: lines
+ * inside a block comment are only recognized if they start with `*`, and in
+ * markup, quotes only start a string (attribute value) inside a tag.
+ *
+ * @param {string} line - The line the link was found on.
+ * @param {number} index - The position of the link in the line.
+ * @param {string|null} language - The language of the file, see getLanguage.
+ * @returns {string} - One of `line-comment`, `block-comment`, `string` or `text`.
+ */
+function getLinkContext(line, index, language) {
+  const syntax = Object.hasOwn(commentSyntaxByLanguage, language)
+    ? commentSyntaxByLanguage[language]
+    : {};
+  // Don't mistake the `//` of the link's own scheme, or of a protocol-relative
+  // link, for a comment.
+  const prefix = line
+    .slice(0, index)
+    .replace(/(?:[a-z][\w+.-]*:)?\/\/$/i, "");
+  if (syntax.block?.[0] === "/*" && /^\s*\*(?!\/)/.test(prefix)) {
+    return "block-comment";
+  }
+
+  let quote = null;
+  let inTag = false;
+  for (let i = 0; i < prefix.length; i++) {
+    const ch = prefix[i];
+    if (quote) {
+      if (ch === "\\" && !syntax.markup) {
+        i++; // Skip the escaped character
+      } else if (ch === quote) {
+        quote = null;
+      }
+      continue;
+    }
+    if (syntax.line && prefix.startsWith(syntax.line, i)) {
+      return "line-comment";
+    }
+    if (syntax.block && prefix.startsWith(syntax.block[0], i)) {
+      const end = prefix.indexOf(syntax.block[1], i + syntax.block[0].length);
+      if (end === -1) {
+        return "block-comment";
+      }
+      i = end + syntax.block[1].length - 1;
+      continue;
+    }
+    if (syntax.markup) {
+      if (ch === "<") {
+        inTag = true;
+      } else if (ch === ">") {
+        inTag = false;
+      } else if (inTag && (ch === '"' || ch === "'")) {
+        quote = ch;
+      }
+      continue;
+    }
+    if (ch === "'" && syntax.charLiterals) {
+      const charLiteral = /^'(?:\\[^']{1,10}|[^\\'])'/.exec(prefix.slice(i));
+      if (charLiteral) {
+        i += charLiteral[0].length - 1;
+      }
+      continue;
+    }
+    if (ch === '"' || ch === "'" || ch === "`") {
+      quote = ch;
+    }
+  }
+  return quote ? "string" : "text";
+}
+
+function convertToIndex(uri, searchfoxData, config) {
+  const excludedDirs = config.excluded_dirs ?? [];
+  const excludedLanguages = config.excluded_languages ?? [];
+  const excludedContexts = config.excluded_link_contexts ?? [];
   const {protocol, hostname, pathname} = uri;
   const domain = hostname;
   const index = new Map();
   const regex = new RegExp(String.raw`${domain}[\S]*#(${fragment_re})`);
   for (const {lines, path: filepath} of searchfoxData) {
+    if (isInExcludedDir(filepath, excludedDirs)) {
+      continue;
+    }
+    const language = getLanguage(filepath);
+    if (excludedLanguages.includes(language)) {
+      continue;
+    }
     for (const {lno: line_number, line} of lines) {
       const match = regex.exec(`${line} `);
       if (!match || match.length < 2) {
@@ -180,22 +384,60 @@ function convertToIndex(uri, searchfoxData) {
         algorithm = algorithm.slice(0, -1).replace(heuristic_re, "$1");;
       }
       const url = `${protocol}//${hostname}${pathname}#${algorithm}`;
-      index.getOrInsert(algorithm, []).push({url, filepath, line_number});
+      const context = getLinkContext(line, match.index, language);
+      if (excludedContexts.includes(context)) {
+        continue;
+      }
+      const todo = todo_re.test(line);
+      index
+        .getOrInsert(normalizeFragment(algorithm, config), [])
+        .push({url, filepath, line_number, todo, language, context});
     }
   }
 
   return index;
 }
 
-function processIndexData(indexData) {
+/**
+ * Displays the SpecMonkey buttons for all fragments in the index, and the
+ * page-level report.
+ *
+ * @param {Map<string, Array<Object>>} indexData - The index, by fragment.
+ * @param {Object} config - The parsed config.json.
+ * @param {boolean} timedOut - Whether the Searchfox query timed out, so the
+ *   index is incomplete.
+ */
+function processIndexData(indexData, config, timedOut) {
+  const normalizedAnchors =
+    config.fold_fragment_case || config.normalize_fragment_unicode
+      ? collectNormalizedAnchors(config)
+      : null;
+  const referencedAnchors = new Set();
+  const untestedLinks = [];
+  const testOnlyLinks = [];
   for (const [fragment, elements] of indexData.entries()) {
     if (Array.isArray(elements)) {
       try {
         const anchor =
           //   document.querySelector(`a[name="${fragment}"], a#${fragment}`) ||
-          findAnchorByIdOrName(fragment);
+          normalizedAnchors?.get(fragment) ?? findAnchorByIdOrName(fragment);
         if (anchor) {
-          displaySpecmonkeyButton(anchor, elements);
+          referencedAnchors.add(anchor);
+          displaySpecmonkeyButton(anchor, elements, config);
+
+          const categories = categorizeLinks(elements, config);
+          const hasCode = categories["Code"].length > 0;
+          const hasTests =
+            categories["Test"].length + categories["Web-Platform Test"].length > 0;
+          const link = {
+            text: fragment,
+            href: `#${anchor.id || anchor.getAttribute("name")}`,
+          };
+          if (hasCode && !hasTests) {
+            untestedLinks.push(link);
+          } else if (hasTests && !hasCode) {
+            testOnlyLinks.push(link);
+          }
         }
       } catch (e) {
         console.warn(e);
@@ -206,6 +448,97 @@ function processIndexData(indexData) {
       );
     }
   }
+
+  const reportSections = [];
+  // Spec coverage: sections whose own anchor is never referenced. References
+  // to definitions inside a section don't count towards the section. Without
+  // any references (e.g. a page that isn't a spec) or with a truncated index,
+  // this would only list referenced sections as unreferenced.
+  const sections = indexData.size > 0 && !timedOut ? collectSections() : [];
+  if (sections.length > 0) {
+    const unreferenced = sections.filter(
+      ({anchor}) => !referencedAnchors.has(anchor)
+    );
+    reportSections.push({
+      title: `${unreferenced.length} of ${sections.length} Sections without References in Gecko`,
+      links: unreferenced.map(({anchor, title}) => ({
+        text: title,
+        href: `#${anchor.id}`,
+      })),
+    });
+  }
+  // Cross-reference implementation and test references per fragment, to
+  // highlight untested spec requirements. Also unreliable with a truncated
+  // index.
+  if (!timedOut && untestedLinks.length > 0) {
+    reportSections.push({
+      title: `${untestedLinks.length} Fragments referenced by Code but not by Tests`,
+      links: untestedLinks,
+    });
+  }
+  if (!timedOut && testOnlyLinks.length > 0) {
+    reportSections.push({
+      title: `${testOnlyLinks.length} Fragments referenced by Tests but not by Code`,
+      links: testOnlyLinks,
+    });
+  }
+  // Spec work remaining: every reference on a line with a TODO marker.
+  const todoLinks = [...indexData.entries()].flatMap(([fragment, elements]) =>
+    elements
+      .filter((element) => element.todo)
+      .map((element) => ({
+        text: `${element.filepath}#${element.line_number} (${fragment})`,
+        href: constructPermalink(config, element.filepath, element.line_number),
+      }))
+  );
+  if (todoLinks.length > 0) {
+    reportSections.push({
+      title: `${todoLinks.length} ${
+        todoLinks.length == 1 ? "Reference" : "References"
+      } with TODOs${timedOut ? " (incomplete, Searchfox query timed out)" : ""}`,
+      links: todoLinks,
+    });
+  }
+  displaySpecmonkeyReport(reportSections);
+}
+
+/**
+ * Collects the sections of the spec page: headings with an id, or headings
+ * whose parent has one (e.g. `<emu-clause id="sec-..."><h1>` in ECMA-262).
+ *
+ * @returns {Array<Object>} - The sections as `{anchor, title}`, in document order.
+ */
+function collectSections() {
+  const sections = new Map();
+  for (const heading of document.querySelectorAll("h1, h2, h3, h4, h5, h6")) {
+    const anchor = heading.id ? heading : heading.parentElement;
+    if (anchor?.id && !sections.has(anchor)) {
+      sections.set(anchor, heading.textContent.trim());
+    }
+  }
+  return [...sections].map(([anchor, title]) => ({anchor, title}));
+}
+
+/**
+ * Maps the normalized id or name of every element on the page to the element,
+ * preferring ids over names like findAnchorByIdOrName does. Ids and names are
+ * percent-decoded and normalized by normalizeFragment, just like fragments.
+ *
+ * @param {Object} config - The parsed config.json.
+ * @returns {Map<string, HTMLElement>} - The anchors by normalized fragment.
+ */
+function collectNormalizedAnchors(config) {
+  const anchors = new Map();
+  for (const element of document.querySelectorAll("[id]")) {
+    anchors.getOrInsert(normalizeFragment(element.id, config), element);
+  }
+  for (const element of document.querySelectorAll("[name]")) {
+    anchors.getOrInsert(
+      normalizeFragment(element.getAttribute("name"), config),
+      element
+    );
+  }
+  return anchors;
 }
 
 function findAnchorByIdOrName(fragment) {
@@ -221,13 +554,83 @@ function findAnchorByIdOrName(fragment) {
   return null;
 }
 
+/**
+ * Displays the page-level SpecMonkey report behind a button fixed to the
+ * bottom right corner of the page.
+ *
+ * @param {Array<Object>} reportSections - The report sections, each with a
+ *   `title` and `links` as `{text, href}`. Links to other pages open in a new tab.
+ */
+function displaySpecmonkeyReport(reportSections) {
+  if (reportSections.length === 0) {
+    return;
+  }
+
+  const reportButton = document.createElement("button");
+  reportButton.classList.add("specmonkey-box-button", "specmonkey-report-button");
+  const reportIcon = document.createElement("img");
+  reportIcon.src = browser.runtime.getURL("searchfox.png");
+  reportIcon.alt = "SpecMonkey Report";
+  reportIcon.classList.add("specmonkey-box-icon");
+  reportButton.appendChild(reportIcon);
+  document.body.appendChild(reportButton);
+
+  const report = document.createElement("div");
+  report.classList.add("specmonkey-box", "specmonkey-report");
+  report.style.display = "none"; // Hidden by default
+  for (const {title, links} of reportSections) {
+    const headline = document.createElement("h3");
+    headline.textContent = title;
+    report.appendChild(headline);
+    for (const {text, href} of links) {
+      const link = document.createElement("a");
+      link.href = href;
+      link.textContent = text;
+      if (!href.startsWith("#")) {
+        link.target = "_blank";
+        link.rel = "noopener noreferrer";
+      }
+      report.appendChild(link);
+    }
+  }
+
+  const closeButton = document.createElement("span");
+  closeButton.textContent = "×";
+  closeButton.classList.add("specmonkey-close-button");
+  report.appendChild(closeButton);
+  document.body.appendChild(report);
+
+  closeButton.addEventListener("click", () => {
+    report.style.display = "none";
+    currentOpenBox = null;
+    document.removeEventListener("click", handleClickOutsideBox);
+  });
+
+  reportButton.addEventListener("click", (event) => {
+    event.stopPropagation(); // Prevent the event from bubbling up
+    if (report.style.display === "block") {
+      report.style.display = "none";
+      currentOpenBox = null;
+      document.removeEventListener("click", handleClickOutsideBox);
+      return;
+    }
+    if (currentOpenBox && currentOpenBox !== report) {
+      currentOpenBox.style.display = "none";
+    }
+    report.style.display = "block";
+    currentOpenBox = report;
+    document.addEventListener("click", handleClickOutsideBox);
+  });
+}
+
 /**
  * Displays the SpecMonkey information box with organized links and a specmonkey button.
  *
  * @param {HTMLElement} anchor - The anchor element near which the box will be displayed.
  * @param {Array<Object>} elements - An array of data elements associated with the anchor.
+ * @param {Object} config - The parsed config.json.
  */
-function displaySpecmonkeyButton(anchor, elements) {
+function displaySpecmonkeyButton(anchor, elements, config) {
   // Check if a box already exists for this anchor to prevent duplicates
   if (
     anchor.nextSibling &&
@@ -268,7 +671,7 @@ function displaySpecmonkeyButton(anchor, elements) {
     infoBox.style.display = "none"; // Hidden by default
 
     // Organize links into categories based on the specified criteria
-    const categorizedLinks = categorizeLinks(elements);
+    const categorizedLinks = categorizeLinks(elements, config);
 
     // Create the information box
     const infoInlay = document.createElement("div");
@@ -282,13 +685,19 @@ function displaySpecmonkeyButton(anchor, elements) {
         )}: ${el[1].length}`;
       })
       .join(" | ");
+    const todoCount = elements.filter((element) => element.todo).length;
+    if (todoCount != 0) {
+      shortCategories += ` | TODO: ${todoCount}`;
+    }
 
     infoInlay.innerText = shortCategories;
     document.body.appendChild(infoInlay);
 
     const headline = document.createElement("h3");
     const searchfoxQuery = document.createElement("a");
-    searchfoxQuery.href = `https://searchfox.org/mozilla-central/search?q=${encodeURIComponent(
+    searchfoxQuery.href = `https://searchfox.org/${getSearchfoxTree(
+      config
+    )}/search?q=${encodeURIComponent(
       elements[0].url
     )}`;
     searchfoxQuery.target = "_blank";
@@ -314,13 +723,30 @@ function displaySpecmonkeyButton(anchor, elements) {
       // Create and append the links
       links.forEach((linkData) => {
         const link = document.createElement("a");
-        link.href = constructSearchfoxURL(
+        link.href = constructPermalink(
+          config,
           linkData.filepath,
           linkData.line_number
         );
         link.textContent = `${linkData.filepath}#${linkData.line_number}`;
         link.target = "_blank";
         link.rel = "noopener noreferrer";
+        if (linkData.language) {
+          const languageBadge = document.createElement("span");
+          languageBadge.classList.add("specmonkey-language-badge");
+          languageBadge.textContent = linkData.language;
+          link.appendChild(languageBadge);
+        }
+        const contextBadge = document.createElement("span");
+        contextBadge.classList.add("specmonkey-context-badge");
+        contextBadge.textContent = linkData.context.replace("-", " ");
+        link.appendChild(contextBadge);
+        if (linkData.todo) {
+          const todoBadge = document.createElement("span");
+          todoBadge.classList.add("specmonkey-todo-badge");
+          todoBadge.textContent = "TODO";
+          link.appendChild(todoBadge);
+        }
 
         infoBox.appendChild(link);
       });
@@ -425,34 +851,72 @@ function displaySpecmonkeyButton(anchor, elements) {
   });
 }
 
+/**
+ * Converts a path glob into a case-insensitive regular expression.
+ * `**` matches across directories, `*` and `?` match within one path segment.
+ *
+ * @param {string} glob - The glob, e.g. `*_test.*`.
+ * @returns {RegExp} - A regular expression matching the whole path.
+ */
+function globToRegExp(glob) {
+  const source = glob
+    .split(/(\*\*\/|\*\*|\*|\?)/)
+    .map((part) => {
+      switch (part) {
+        case "**/": return "(?:.*/)?";
+        case "**": return ".*";
+        case "*": return "[^/]*";
+        case "?": return "[^/]";
+        default: return part.replace(/[.+^${}()|[\]\\]/g, "\\$&");
+      }
+    })
+    .join("");
+  return new RegExp(`^${source}$`, "i");
+}
+
+// Globs for test files, used unless config.json has `test_paths`.
+const defaultTestPaths = [
+  "testing/**",
+  "**/test/**",
+  "**/tests/**",
+  "**/mochitest/**",
+  "**/reftests/**",
+  "**/crashtests/**",
+  "**/gtest/**",
+  "**/test_*",
+  "**/*_test.*",
+  "**/browser_*.js",
+];
+
 /**
  * Categorizes links based on specified criteria.
  *
+ * A file is a test if its path matches any of the `test_paths` globs from
+ * config.json, or defaultTestPaths if there are none.
+ *
  * @param {Array<Object>} elements - An array of data elements containing filepath and line_number.
+ * @param {Object} config - The parsed config.json.
  * @returns {Object} - An object containing categorized links.
  */
-function categorizeLinks(elements) {
+function categorizeLinks(elements, config) {
   const categories = {
     "Web-Platform Test": [],
     Test: [],
     Code: [],
   };
 
-  // Define the file extensions for Mochitest
-  const mochitestExtensions = [".html", ".xhtml", ".js"];
+  const testPathRegExps = (config.test_paths ?? defaultTestPaths).map(
+    globToRegExp
+  );
 
   // Iterate through each element and categorize it
   elements.forEach((element) => {
     const filepath = element.filepath;
     const lowerPath = filepath.toLowerCase();
-    const extension = `.${lowerPath.split(".").pop()}`;
 
     if (lowerPath.startsWith("testing/web-platform")) {
       categories["Web-Platform Test"].push(element);
-    } else if (
-      lowerPath.includes("test") &&
-      mochitestExtensions.includes(extension)
-    ) {
+    } else if (testPathRegExps.some((regex) => regex.test(lowerPath))) {
       categories["Test"].push(element);
     } else {
       categories["Code"].push(element);
@@ -462,12 +926,99 @@ function categorizeLinks(elements) {
   return categories;
 }
 
-function constructSearchfoxURL(filepath, lineNumber) {
-  // Construct the Searchfox URL based on the filepath and line number
+// Built-in permalink templates, selectable by name via `permalink_template`
+// in config.json. `{url}` and `{commit}` come from `source_repository`,
+// `{tree}` is its Searchfox tree.
+const permalinkTemplates = {
+  searchfox: "https://searchfox.org/{tree}/source/{path}#{line}",
+  github: "{url}/blob/{commit}/{path}#L{line}",
+  gitlab: "{url}/-/blob/{commit}/{path}#L{line}",
+};
+
+/**
+ * Returns the template string selected by `config.permalink_template`, which
+ * is either the name of a built-in template or a custom template string.
+ * Defaults to the `searchfox` template.
+ *
+ * @param {Object} config - The parsed config.json.
+ * @returns {string|null} - The template, or null if it is neither.
+ */
+function getPermalinkTemplate(config) {
+  const template = config.permalink_template ?? "searchfox";
+  if (Object.hasOwn(permalinkTemplates, template)) {
+    return permalinkTemplates[template];
+  }
+  if (typeof template === "string" && /\{\w+\}/.test(template)) {
+    return template;
+  }
+  return null;
+}
+
+/**
+ * Returns the placeholder values that come from config.json. `{commit}` is
+ * `source_repository.commit`. `{revision}` is the commit if set, otherwise the
+ * branch, in which case links follow the branch rather than being permalinks.
+ *
+ * @param {Object} config - The parsed config.json.
+ * @returns {Object} - The values for `{url}`, `{commit}`, `{revision}` and `{tree}`.
+ */
+function getRepositoryPlaceholders(config) {
+  const {url, branch, commit} = config.source_repository ?? {};
+  return {
+    url: url?.replace(/\/+$/, ""),
+    commit,
+    revision: commit ?? branch,
+    tree: getSearchfoxTree(config),
+  };
+}
 
-  const baseURL = "https://searchfox.org/"; // Replace with your Searchfox base URL if different
-  const repository = "mozilla-central"; // Replace with your repository name
-  //   const filePath = encodeURIComponent(filepath);
-  const searchfoxURL = `${baseURL}${repository}/source/${filepath}#${lineNumber}`;
-  return searchfoxURL;
+/**
+ * Falls back to the `searchfox` template if `config.permalink_template` is
+ * unknown or needs a `source_repository` value that isn't configured, so that
+ * links never end up with a bogus href.
+ *
+ * @param {Object} config - The parsed config.json, updated in place.
+ */
+function validatePermalinkTemplate(config) {
+  const template = getPermalinkTemplate(config);
+  if (template === null) {
+    console.warn(
+      `SpecMonkey: Unknown permalink_template '${config.permalink_template}', using 'searchfox'.`
+    );
+    config.permalink_template = "searchfox";
+    return;
+  }
+  const values = getRepositoryPlaceholders(config);
+  for (const [, key] of template.matchAll(/\{(\w+)\}/g)) {
+    if (Object.hasOwn(values, key) && !values[key]) {
+      console.warn(
+        `SpecMonkey: permalink_template '${config.permalink_template}' needs '{${key}}' from source_repository, using 'searchfox'.`
+      );
+      config.permalink_template = "searchfox";
+      return;
+    }
+  }
+}
+
+/**
+ * Constructs a web permalink for a link location.
+ *
+ * Custom templates can use `{url}`, `{commit}`, `{revision}`, `{tree}`,
+ * `{path}` and `{line}`; see getRepositoryPlaceholders.
+ *
+ * @param {Object} config - The parsed config.json.
+ * @param {string} filepath - The path of the file, relative to the repository root.
+ * @param {number} lineNumber - The line number of the link.
+ * @returns {string} - The permalink URL.
+ */
+function constructPermalink(config, filepath, lineNumber) {
+  const template = getPermalinkTemplate(config) ?? permalinkTemplates.searchfox;
+  const values = {
+    ...getRepositoryPlaceholders(config),
+    path: filepath,
+    line: lineNumber,
+  };
+  return template.replace(/\{(\w+)\}/g, (placeholder, key) =>
+    Object.hasOwn(values, key) ? values[key] : placeholder
+  );
 }